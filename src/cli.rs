@@ -0,0 +1,95 @@
+use clap::{Parser, ValueEnum};
+
+/// Render a Project Zomboid `worldmap.xml` to SVG, PNG, or a tiled pyramid.
+#[derive(Parser, Debug)]
+#[command(about = "Render a Project Zomboid worldmap.xml to SVG, PNG, or a tiled pyramid")]
+pub struct Cli {
+    /// Path to the worldmap.xml to render.
+    #[arg(long)]
+    pub input: String,
+
+    /// Directory to write output files into.
+    #[arg(long, default_value = ".")]
+    pub out_dir: String,
+
+    /// Comma-separated layer names to write; defaults to every layer encountered.
+    #[arg(long, value_delimiter = ',')]
+    pub layers: Option<Vec<String>>,
+
+    /// Region to export, in cell coordinates: minx,miny,maxx,maxy.
+    #[arg(long)]
+    pub region: Option<String>,
+
+    /// Path to the style sheet TOML file.
+    #[arg(long, default_value = "styles.toml")]
+    pub style: String,
+
+    /// Output format.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Svg)]
+    pub format: OutputFormat,
+}
+
+#[derive(ValueEnum, Clone, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Svg,
+    Png,
+    /// A `z/x/y` tiled pyramid of SVGs, for a Leaflet-style viewer.
+    Tiles,
+}
+
+impl Cli {
+    /// Parses `--region` into a `(min_x, min_y, max_x, max_y)` cell-coordinate tuple.
+    pub fn region_cells(&self) -> Result<Option<(i32, i32, i32, i32)>, Box<dyn std::error::Error>> {
+        let Some(region) = &self.region else {
+            return Ok(None);
+        };
+
+        let parts: Vec<i32> = region
+            .split(',')
+            .map(|part| part.trim().parse())
+            .collect::<Result<_, _>>()
+            .map_err(|_| format!("--region expects minx,miny,maxx,maxy, got '{region}'"))?;
+
+        let [min_x, min_y, max_x, max_y] = parts[..] else {
+            return Err(format!("--region expects minx,miny,maxx,maxy, got '{region}'").into());
+        };
+
+        Ok(Some((min_x, min_y, max_x, max_y)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cli_with_region(region: Option<&str>) -> Cli {
+        Cli {
+            input: "worldmap.xml".into(),
+            out_dir: ".".into(),
+            layers: None,
+            region: region.map(String::from),
+            style: "styles.toml".into(),
+            format: OutputFormat::Svg,
+        }
+    }
+
+    #[test]
+    fn no_region_is_none() {
+        assert_eq!(cli_with_region(None).region_cells().unwrap(), None);
+    }
+
+    #[test]
+    fn parses_four_comma_separated_cells() {
+        assert_eq!(cli_with_region(Some("1,2,3,4")).region_cells().unwrap(), Some((1, 2, 3, 4)));
+    }
+
+    #[test]
+    fn rejects_wrong_number_of_parts() {
+        assert!(cli_with_region(Some("1,2,3")).region_cells().is_err());
+    }
+
+    #[test]
+    fn rejects_non_integer_parts() {
+        assert!(cli_with_region(Some("1,2,x,4")).region_cells().is_err());
+    }
+}
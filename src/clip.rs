@@ -0,0 +1,95 @@
+use crate::Point;
+use crate::quadtree::Rect;
+
+fn lerp_at_x(s: &Point, e: &Point, x: i32) -> Point {
+    let t = (x - s.x) as f64 / (e.x - s.x) as f64;
+    Point {
+        x,
+        y: (s.y as f64 + t * (e.y - s.y) as f64).round() as i32,
+    }
+}
+
+fn lerp_at_y(s: &Point, e: &Point, y: i32) -> Point {
+    let t = (y - s.y) as f64 / (e.y - s.y) as f64;
+    Point {
+        x: (s.x as f64 + t * (e.x - s.x) as f64).round() as i32,
+        y,
+    }
+}
+
+/// One pass of Sutherland-Hodgman against a single clip edge: walk the
+/// polygon and, for each consecutive pair (s, e), emit e when it is inside
+/// the edge, and emit the edge/segment intersection whenever the
+/// inside-status changes between s and e.
+fn clip_edge(points: &[Point], inside: impl Fn(&Point) -> bool, intersect: impl Fn(&Point, &Point) -> Point) -> Vec<Point> {
+    if points.is_empty() {
+        return Vec::new();
+    }
+
+    let mut output = Vec::with_capacity(points.len());
+    let mut s = points[points.len() - 1].clone();
+
+    for e in points {
+        if inside(e) {
+            if !inside(&s) {
+                output.push(intersect(&s, e));
+            }
+            output.push(e.clone());
+        } else if inside(&s) {
+            output.push(intersect(&s, e));
+        }
+        s = e.clone();
+    }
+
+    output
+}
+
+/// Clips a polygon to `clip` using the Sutherland-Hodgman algorithm, applying
+/// each of the four clip edges in turn so a polygon straddling the viewport
+/// boundary is trimmed to the rectangle rather than drawn whole.
+pub fn clip_polygon(points: &[Point], clip: &Rect) -> Vec<Point> {
+    let mut points = points.to_vec();
+
+    points = clip_edge(&points, |p| p.x >= clip.min_x, |s, e| lerp_at_x(s, e, clip.min_x));
+    points = clip_edge(&points, |p| p.x <= clip.max_x, |s, e| lerp_at_x(s, e, clip.max_x));
+    points = clip_edge(&points, |p| p.y >= clip.min_y, |s, e| lerp_at_y(s, e, clip.min_y));
+    points = clip_edge(&points, |p| p.y <= clip.max_y, |s, e| lerp_at_y(s, e, clip.max_y));
+
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(x: i32, y: i32) -> Point {
+        Point { x, y }
+    }
+
+    #[test]
+    fn polygon_fully_inside_clip_is_unchanged() {
+        let square = vec![point(10, 10), point(20, 10), point(20, 20), point(10, 20)];
+        let clip = Rect { min_x: 0, min_y: 0, max_x: 100, max_y: 100 };
+
+        assert_eq!(clip_polygon(&square, &clip), square);
+    }
+
+    #[test]
+    fn polygon_straddling_clip_edge_is_trimmed() {
+        let square = vec![point(0, 0), point(20, 0), point(20, 20), point(0, 20)];
+        let clip = Rect { min_x: 10, min_y: 10, max_x: 30, max_y: 30 };
+
+        let clipped = clip_polygon(&square, &clip);
+
+        assert!(clipped.iter().all(|p| p.x >= clip.min_x && p.y >= clip.min_y));
+        assert_eq!(clipped, vec![point(10, 10), point(20, 10), point(20, 20), point(10, 20)]);
+    }
+
+    #[test]
+    fn polygon_entirely_outside_clip_becomes_empty() {
+        let square = vec![point(0, 0), point(5, 0), point(5, 5), point(0, 5)];
+        let clip = Rect { min_x: 100, min_y: 100, max_x: 200, max_y: 200 };
+
+        assert!(clip_polygon(&square, &clip).is_empty());
+    }
+}
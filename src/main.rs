@@ -1,11 +1,23 @@
+mod cli;
+mod clip;
+mod quadtree;
+mod raster;
+mod style;
+mod tiling;
+
 use std::collections::BTreeMap;
 use std::str::FromStr;
 
+use clap::Parser;
 use serde::{Deserialize, Deserializer};
 
 use svg::Document;
 use svg::Node;
-use svg::node::element::{Polygon, Rectangle, Text};
+use svg::node::element::{Polygon, Polyline, Rectangle, Text};
+
+use clip::clip_polygon;
+use quadtree::{QuadTree, Rect};
+use style::{ResolvedStyle, StyleSheet};
 
 #[derive(Deserialize, Debug)]
 pub struct World {
@@ -13,10 +25,40 @@ pub struct World {
 }
 
 impl World {
-    pub fn render(&self, svg_layers: &mut SVGLayers) {
+    pub fn render(&self, svg_layers: &mut SVGLayers, styles: &StyleSheet) {
+        for cell in &self.cell {
+            cell.render(svg_layers, styles);
+        }
+    }
+
+    /// Renders only the features overlapping `clip`, via a quadtree built
+    /// over every feature's bounding box.
+    pub fn render_region(&self, svg_layers: &mut SVGLayers, styles: &StyleSheet, clip: Rect) {
+        let mut entries: Vec<(&Feature, Point, Rect)> = Vec::new();
+        let mut world_bounds: Option<Rect> = None;
+
         for cell in &self.cell {
-            cell.render(svg_layers);
+            let bottom_left = cell.bottom_left();
+            for feature in &cell.feature {
+                let bbox = feature.bounding_box(&bottom_left);
+                world_bounds = Some(match world_bounds {
+                    Some(bounds) => bounds.union(&bbox),
+                    None => bbox.clone(),
+                });
+                entries.push((feature, bottom_left.clone(), bbox));
+            }
+        }
+
+        let Some(world_bounds) = world_bounds else {
+            return;
+        };
+
+        let mut tree = QuadTree::new(world_bounds);
+        for (feature, bottom_left, bbox) in entries {
+            tree.insert(feature, bottom_left, bbox);
         }
+
+        tree.query_region(&clip, svg_layers, styles);
     }
 }
 
@@ -36,9 +78,9 @@ impl Cell {
         }
     }
 
-    pub fn render(&self, svg_layers: &mut SVGLayers) {
+    pub fn render(&self, svg_layers: &mut SVGLayers, styles: &StyleSheet) {
         for feature in &self.feature {
-            feature.render(svg_layers, &self.bottom_left())
+            feature.render(svg_layers, &self.bottom_left(), styles)
         }
     }
 }
@@ -51,8 +93,45 @@ pub struct Feature {
 }
 
 impl Feature {
-    pub fn render(&self, svg_layers: &mut SVGLayers, bottom_left: &Point) {
-        self.geometry.render(svg_layers, bottom_left, &self.properties)
+    pub fn render(&self, svg_layers: &mut SVGLayers, bottom_left: &Point, styles: &StyleSheet) {
+        self.geometry.render(svg_layers, bottom_left, &self.properties, styles)
+    }
+
+    /// The bounding box of every point in this feature's geometry, adjusted
+    /// for the cell's `bottom_left` offset.
+    pub fn bounding_box(&self, bottom_left: &Point) -> Rect {
+        let mut min_x = i32::MAX;
+        let mut max_x = i32::MIN;
+        let mut min_y = i32::MAX;
+        let mut max_y = i32::MIN;
+
+        for coordinate in &self.geometry.coordinates {
+            for point in &coordinate.point {
+                let adjusted = bottom_left.add(point);
+                min_x = min_x.min(adjusted.x);
+                max_x = max_x.max(adjusted.x);
+                min_y = min_y.min(adjusted.y);
+                max_y = max_y.max(adjusted.y);
+            }
+        }
+
+        Rect { min_x, min_y, max_x, max_y }
+    }
+
+    /// Renders this feature against `clip`: polygons are trimmed with
+    /// Sutherland-Hodgman, while points and lines (already known to overlap
+    /// the clip rectangle by bounding box) render unclipped.
+    pub fn render_clipped(&self, svg_layers: &mut SVGLayers, bottom_left: &Point, styles: &StyleSheet, clip: &Rect) {
+        match self.geometry.geometry_type {
+            GeometryType::Polygon => {
+                for coordinate in &self.geometry.coordinates {
+                    coordinate.render_polygon_clipped(svg_layers, bottom_left, &self.properties, styles, clip);
+                }
+            }
+            GeometryType::LineString | GeometryType::Point => {
+                self.render(svg_layers, bottom_left, styles);
+            }
+        }
     }
 }
 
@@ -66,12 +145,15 @@ pub enum GeometryType {
 impl FromStr for GeometryType {
     type Err = std::io::Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(match s {
-            "LineString" => GeometryType::LineString,
-            "Polygon" => GeometryType::Polygon,
-            "Point" => GeometryType::Point,
-            _ => panic!("wat"),
-        })
+        match s {
+            "LineString" => Ok(GeometryType::LineString),
+            "Polygon" => Ok(GeometryType::Polygon),
+            "Point" => Ok(GeometryType::Point),
+            other => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("unknown geometry type '{other}'"),
+            )),
+        }
     }
 }
 
@@ -94,16 +176,17 @@ pub struct Geometry {
 }
 
 impl Geometry {
-    pub fn render(&self, svg_layers: &mut SVGLayers, bottom_left: &Point, properties: &Option<Properties>) {
+    pub fn render(&self, svg_layers: &mut SVGLayers, bottom_left: &Point, properties: &Option<Properties>, styles: &StyleSheet) {
         for coordinate in &self.coordinates {
             match self.geometry_type {
                 GeometryType::LineString => {
+                    coordinate.render_polyline(svg_layers, bottom_left, properties, styles)
                 },
                 GeometryType::Polygon => {
-                    coordinate.render_polygon(svg_layers, bottom_left, properties)
+                    coordinate.render_polygon(svg_layers, bottom_left, properties, styles)
                 },
                 GeometryType::Point => {
-                    coordinate.render_point(svg_layers, bottom_left, properties)
+                    coordinate.render_point(svg_layers, bottom_left, properties, styles)
                 }
             }
         }
@@ -115,8 +198,23 @@ pub struct Coordinates {
     pub point: Vec<Point>,
 }
 
+fn build_polygon(points: String, resolved: &ResolvedStyle) -> Polygon {
+    let mut polygon = Polygon::new();
+
+    polygon.assign("fill", resolved.fill.clone().unwrap_or_else(|| "none".into()));
+
+    if let Some(stroke) = &resolved.stroke {
+        polygon.assign("stroke", stroke.clone());
+        polygon.assign("stroke-width", resolved.stroke_width.unwrap_or(2));
+    }
+
+    polygon.assign("points", points);
+
+    polygon
+}
+
 impl Coordinates {
-    pub fn render_polygon(&self, svg_layers: &mut SVGLayers, bottom_left: &Point, properties: &Option<Properties>) {
+    pub fn render_polygon(&self, svg_layers: &mut SVGLayers, bottom_left: &Point, properties: &Option<Properties>, styles: &StyleSheet) {
         let points: String = self.point
             .iter()
             .map(|p| {
@@ -126,46 +224,57 @@ impl Coordinates {
             .collect::<Vec<String>>()
             .join(" ");
 
-        let mut fill = "none";
-        let mut stroke: Option<String> = Some("black".into());
-        let mut layer_key = "polygons".into();
+        let resolved = styles.resolve(properties);
+        let polygon = build_polygon(points, &resolved);
 
-        if let Some(properties) = properties {
-            for property in &properties.property {
-                /*
-                 */
-                if property.name == "water" {
-                    layer_key = "water".into();
-                    fill = "blue";
-                    stroke = None;
-                } else if property.name == "natural" && property.value == "wood" {
-                    fill = "green";
-                    stroke = None;
-                } else if property.name == "building" {
-                    if property.value == "Medical" {
-                        layer_key = "medical".into();
-                        fill = "red";
-                        stroke = None;
-                    }
-                }
-            }
+        svg_layers.add_to_layer(resolved.layer, polygon.into(), resolved.z_index);
+    }
+
+    /// As `render_polygon`, but trims the polygon to `clip` with
+    /// Sutherland-Hodgman first, dropping it entirely if nothing remains.
+    pub fn render_polygon_clipped(&self, svg_layers: &mut SVGLayers, bottom_left: &Point, properties: &Option<Properties>, styles: &StyleSheet, clip: &Rect) {
+        let adjusted: Vec<Point> = self.point.iter().map(|p| bottom_left.add(p)).collect();
+        let clipped = clip_polygon(&adjusted, clip);
+
+        if clipped.len() < 3 {
+            return;
         }
 
-        let mut polygon = Polygon::new();
+        let points = clipped
+            .iter()
+            .map(|p| format!("{},{}", p.x, p.y))
+            .collect::<Vec<String>>()
+            .join(" ");
 
-        polygon.assign("fill", fill);
+        let resolved = styles.resolve(properties);
+        let polygon = build_polygon(points, &resolved);
 
-        if let Some(stroke) = stroke {
-            polygon.assign("stroke", stroke);
-            polygon.assign("stroke-width", 2);
-        }
+        svg_layers.add_to_layer(resolved.layer, polygon.into(), resolved.z_index);
+    }
+
+    pub fn render_polyline(&self, svg_layers: &mut SVGLayers, bottom_left: &Point, properties: &Option<Properties>, styles: &StyleSheet) {
+        let points: String = self.point
+            .iter()
+            .map(|p| {
+                let adjusted_point = bottom_left.add(&p);
+                format!("{},{}", adjusted_point.x, adjusted_point.y)
+            })
+            .collect::<Vec<String>>()
+            .join(" ");
+
+        let resolved = styles.resolve(properties);
+
+        let mut polyline = Polyline::new();
 
-        polygon.assign("points", points);
+        polyline.assign("fill", "none");
+        polyline.assign("stroke", resolved.stroke.unwrap_or_else(|| "gray".into()));
+        polyline.assign("stroke-width", resolved.stroke_width.unwrap_or(2));
+        polyline.assign("points", points);
 
-        svg_layers.add_to_layer(layer_key, polygon.into());
+        svg_layers.add_to_layer(resolved.layer, polyline.into(), resolved.z_index);
     }
 
-    pub fn render_point(&self, svg_layers: &mut SVGLayers, bottom_left: &Point, properties: &Option<Properties>) {
+    pub fn render_point(&self, svg_layers: &mut SVGLayers, bottom_left: &Point, properties: &Option<Properties>, styles: &StyleSheet) {
         assert_eq!(self.point.len(), 1);
         let point = &self.point[0];
         let adjusted_point = bottom_left.add(&point);
@@ -173,15 +282,17 @@ impl Coordinates {
         if let Some(properties) = properties {
             for property in &properties.property {
                 if property.name == "name_en" {
+                    let resolved = styles.resolve_property(property);
+
                     let mut text = Text::new();
                     text.assign("x", adjusted_point.x);
                     text.assign("y", adjusted_point.y);
                     text.assign("font-family", "Verdana");
-                    text.assign("font-size", "64");
-                    text.assign("fill", "blue");
+                    text.assign("font-size", resolved.font_size.unwrap_or(64));
+                    text.assign("fill", resolved.fill.unwrap_or_else(|| "blue".into()));
                     text.append(svg::node::Text::new(property.value.clone()));
 
-                    svg_layers.add_to_layer("text".into(), text.into());
+                    svg_layers.add_to_layer(resolved.layer, text.into(), resolved.z_index);
                 }
             }
         }
@@ -222,6 +333,12 @@ pub struct SVGLayers {
     max_y: i32,
 
     pub layers: BTreeMap<String, Document>,
+
+    /// Elements bound for the combined `map` document, tagged with their
+    /// z-index. Kept pending (rather than appended immediately) so they can
+    /// be stable-sorted by z-index before the document is built, regardless
+    /// of the order features were encountered in the XML.
+    map_elements: Vec<(u32, svg::node::element::Element)>,
 }
 
 impl SVGLayers {
@@ -232,34 +349,75 @@ impl SVGLayers {
             max_x,
             max_y,
             layers: BTreeMap::default(),
+            map_elements: Vec::new(),
         }
     }
 
+    fn width(&self) -> i32 {
+        self.max_x - self.min_x
+    }
+
+    fn height(&self) -> i32 {
+        self.max_y - self.min_y
+    }
+
     fn get_layer(&mut self, key: String) -> &mut Document {
+        let (min_x, min_y, width, height) = (self.min_x, self.min_y, self.width(), self.height());
         self.layers.entry(key).or_insert_with(|| {
             Document::new()
-                .set("viewBox", (self.min_x, self.min_y, self.max_x, self.max_y))
+                .set("viewBox", (min_x, min_y, width, height))
         })
     }
 
-    pub fn add_to_layer(&mut self, key: String, node: svg::node::element::Element) {
+    pub fn add_to_layer(&mut self, key: String, node: svg::node::element::Element, z_index: u32) {
         self.get_layer(key).append(node.clone());
-        self.get_layer("map".into()).append(node);
+        self.map_elements.push((z_index, node));
     }
 
-    pub fn save(&self) {
-        for (key, layer) in &self.layers {
-            svg::save(format!("{}.svg", key), layer).unwrap();
+    /// Builds the combined `map` document by stable-sorting every pending
+    /// element by z-index, so e.g. water (z-index 0) never paints over a
+    /// building (z-index 3) regardless of feature-encounter order.
+    fn build_map_document(&self) -> Document {
+        let mut elements: Vec<&(u32, svg::node::element::Element)> = self.map_elements.iter().collect();
+        elements.sort_by_key(|(z_index, _)| *z_index);
+
+        let mut document = Document::new().set("viewBox", (self.min_x, self.min_y, self.width(), self.height()));
+        for (_, element) in elements {
+            document.append(element.clone());
         }
+
+        document
     }
-}
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let file_path = "/home/jwm/GOG Games/Project Zomboid/game/projectzomboid/media/maps/Muldraugh, KY/worldmap.xml";
-    let file_str = std::fs::read_to_string(file_path)?;
+    /// Writes each layer (and the combined `map` layer) to `out_dir` as its
+    /// own SVG file, restricted to `layers` when given.
+    pub fn save(&self, out_dir: &str, layers: Option<&[String]>) -> std::io::Result<()> {
+        let wanted = |key: &str| match layers {
+            Some(layers) => layers.iter().any(|l| l == key),
+            None => true,
+        };
 
-    let xml: World = quick_xml::de::from_str(&file_str)?;
+        for (key, layer) in &self.layers {
+            if wanted(key) {
+                svg::save(format!("{out_dir}/{key}.svg"), layer)?;
+            }
+        }
+
+        if wanted("map") {
+            svg::save(format!("{out_dir}/map.svg"), &self.build_map_document())?;
+        }
+
+        Ok(())
+    }
+
+    /// Saves just the combined `map` layer to `path`, e.g. for a single
+    /// tile in a pyramid export.
+    pub fn save_combined(&self, path: &str) -> std::io::Result<()> {
+        svg::save(path, &self.build_map_document())
+    }
+}
 
+fn world_bounds(xml: &World) -> Rect {
     let mut min_cell_x = 0;
     let mut max_cell_x = 0;
     let mut min_cell_y = 0;
@@ -276,24 +434,66 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("{} cells", xml.cell.len());
     println!("{} {} {} {}", min_cell_x, max_cell_x, min_cell_y, max_cell_y);
 
-    let mut svg_layers = SVGLayers::new(
-        min_cell_x * 300, min_cell_y * 300,
-        max_cell_x * 300, max_cell_y * 300,
-    );
+    Rect {
+        min_x: min_cell_x * 300,
+        min_y: min_cell_y * 300,
+        max_x: max_cell_x * 300,
+        max_y: max_cell_y * 300,
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = cli::Cli::parse();
+
+    let file_str = std::fs::read_to_string(&cli.input)?;
+    let xml: World = quick_xml::de::from_str(&file_str)?;
+
+    let styles = StyleSheet::load(&cli.style)?;
+
+    std::fs::create_dir_all(&cli.out_dir)?;
+
+    let region = cli.region_cells()?;
+    let bounds = match region {
+        Some((min_x, min_y, max_x, max_y)) => Rect {
+            min_x: min_x * 300,
+            min_y: min_y * 300,
+            max_x: max_x * 300,
+            max_y: max_y * 300,
+        },
+        None => world_bounds(&xml),
+    };
+
+    let mut svg_layers = SVGLayers::new(bounds.min_x, bounds.min_y, bounds.max_x, bounds.max_y);
 
     svg_layers.add_to_layer(
         "background".into(),
         Rectangle::new()
-            .set("x", 0)
-            .set("y", 0)
-            .set("width", max_cell_x * 300)
-            .set("height", max_cell_y * 300)
+            .set("x", bounds.min_x)
+            .set("y", bounds.min_y)
+            .set("width", bounds.width())
+            .set("height", bounds.height())
             .set("fill", "white").into(),
+        0,
         );
 
-    xml.render(&mut svg_layers);
+    if region.is_some() {
+        xml.render_region(&mut svg_layers, &styles, bounds.clone());
+    } else {
+        xml.render(&mut svg_layers, &styles);
+    }
 
-    svg_layers.save();
+    match cli.format {
+        cli::OutputFormat::Svg => {
+            svg_layers.save(&cli.out_dir, cli.layers.as_deref())?;
+        }
+        cli::OutputFormat::Png => {
+            let image = raster::render_raster(&xml, &styles, &bounds, bounds.width() as u32, bounds.height() as u32)?;
+            image.save(format!("{}/map.png", cli.out_dir))?;
+        }
+        cli::OutputFormat::Tiles => {
+            tiling::export_pyramid(&xml, &styles, &bounds, &tiling::PyramidConfig::default(), &cli.out_dir)?;
+        }
+    }
 
     Ok(())
 }
@@ -0,0 +1,194 @@
+use crate::style::StyleSheet;
+use crate::{Feature, Point, SVGLayers};
+
+/// An axis-aligned rectangle in world pixel coordinates, used both as the
+/// quadtree's node bounds and as the viewport passed to `World::render_region`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rect {
+    pub min_x: i32,
+    pub min_y: i32,
+    pub max_x: i32,
+    pub max_y: i32,
+}
+
+impl Rect {
+    pub fn width(&self) -> i32 {
+        self.max_x - self.min_x
+    }
+
+    pub fn height(&self) -> i32 {
+        self.max_y - self.min_y
+    }
+
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.min_x <= other.max_x
+            && self.max_x >= other.min_x
+            && self.min_y <= other.max_y
+            && self.max_y >= other.min_y
+    }
+
+    pub fn contains_rect(&self, other: &Rect) -> bool {
+        other.min_x >= self.min_x
+            && other.max_x <= self.max_x
+            && other.min_y >= self.min_y
+            && other.max_y <= self.max_y
+    }
+
+    pub fn union(&self, other: &Rect) -> Rect {
+        Rect {
+            min_x: self.min_x.min(other.min_x),
+            min_y: self.min_y.min(other.min_y),
+            max_x: self.max_x.max(other.max_x),
+            max_y: self.max_y.max(other.max_y),
+        }
+    }
+
+    /// Splits into four equal child rectangles (NW, NE, SW, SE).
+    fn quadrants(&self) -> [Rect; 4] {
+        let mid_x = (self.min_x + self.max_x) / 2;
+        let mid_y = (self.min_y + self.max_y) / 2;
+
+        [
+            Rect { min_x: self.min_x, min_y: self.min_y, max_x: mid_x, max_y: mid_y },
+            Rect { min_x: mid_x, min_y: self.min_y, max_x: self.max_x, max_y: mid_y },
+            Rect { min_x: self.min_x, min_y: mid_y, max_x: mid_x, max_y: self.max_y },
+            Rect { min_x: mid_x, min_y: mid_y, max_x: self.max_x, max_y: self.max_y },
+        ]
+    }
+}
+
+const MAX_DEPTH: u32 = 8;
+const MIN_NODE_SIZE: i32 = 300;
+
+/// A quadtree over feature bounding boxes, used to cheaply find the features
+/// that overlap a clip rectangle without visiting the whole world.
+pub struct QuadTree<'a> {
+    bounds: Rect,
+    items: Vec<(&'a Feature, Point, Rect)>,
+    children: Option<Box<[QuadTree<'a>; 4]>>,
+}
+
+impl<'a> QuadTree<'a> {
+    pub fn new(bounds: Rect) -> Self {
+        Self {
+            bounds,
+            items: Vec::new(),
+            children: None,
+        }
+    }
+
+    /// Inserts a feature into the deepest node whose rectangle fully
+    /// contains `bbox`.
+    pub fn insert(&mut self, feature: &'a Feature, bottom_left: Point, bbox: Rect) {
+        self.insert_at(feature, bottom_left, bbox, 0);
+    }
+
+    fn insert_at(&mut self, feature: &'a Feature, bottom_left: Point, bbox: Rect, depth: u32) {
+        if depth < MAX_DEPTH && self.bounds.width() > MIN_NODE_SIZE && self.bounds.height() > MIN_NODE_SIZE {
+            if self.children.is_none() {
+                self.subdivide();
+            }
+
+            if let Some(children) = &mut self.children {
+                for child in children.iter_mut() {
+                    if child.bounds.contains_rect(&bbox) {
+                        child.insert_at(feature, bottom_left, bbox, depth + 1);
+                        return;
+                    }
+                }
+            }
+        }
+
+        self.items.push((feature, bottom_left, bbox));
+    }
+
+    fn subdivide(&mut self) {
+        self.children = Some(Box::new(self.bounds.quadrants().map(QuadTree::new)));
+    }
+
+    /// Renders every feature whose bounding box intersects `clip`, descending
+    /// only into child nodes whose rectangle also intersects it.
+    pub fn query_region(&self, clip: &Rect, svg_layers: &mut SVGLayers, styles: &StyleSheet) {
+        if !self.bounds.intersects(clip) {
+            return;
+        }
+
+        for (feature, bottom_left, bbox) in &self.items {
+            if bbox.intersects(clip) {
+                feature.render_clipped(svg_layers, bottom_left, styles, clip);
+            }
+        }
+
+        if let Some(children) = &self.children {
+            for child in children.iter() {
+                child.query_region(clip, svg_layers, styles);
+            }
+        }
+    }
+
+    /// Collects every `(feature, bottom_left)` entry whose bounding box
+    /// intersects `clip`, descending only into child nodes whose rectangle
+    /// also intersects it. Unlike `query_region`, this doesn't render - it's
+    /// for callers like the raster exporter that need the filtered feature
+    /// list rather than an SVG layer to draw straight into.
+    pub fn query_entries(&self, clip: &Rect, out: &mut Vec<(&'a Feature, &Point)>) {
+        if !self.bounds.intersects(clip) {
+            return;
+        }
+
+        for (feature, bottom_left, bbox) in &self.items {
+            if bbox.intersects(clip) {
+                out.push((feature, bottom_left));
+            }
+        }
+
+        if let Some(children) = &self.children {
+            for child in children.iter() {
+                child.query_entries(clip, out);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Geometry, GeometryType};
+
+    fn feature() -> Feature {
+        Feature {
+            geometry: Geometry { geometry_type: GeometryType::Polygon, coordinates: vec![] },
+            properties: None,
+        }
+    }
+
+    #[test]
+    fn query_entries_finds_only_bboxes_intersecting_the_clip() {
+        let mut tree = QuadTree::new(Rect { min_x: 0, min_y: 0, max_x: 1000, max_y: 1000 });
+
+        let near = feature();
+        tree.insert(&near, Point { x: 0, y: 0 }, Rect { min_x: 10, min_y: 10, max_x: 20, max_y: 20 });
+
+        let far = feature();
+        tree.insert(&far, Point { x: 0, y: 0 }, Rect { min_x: 900, min_y: 900, max_x: 910, max_y: 910 });
+
+        let mut matched = Vec::new();
+        tree.query_entries(&Rect { min_x: 0, min_y: 0, max_x: 50, max_y: 50 }, &mut matched);
+
+        assert_eq!(matched.len(), 1);
+        assert!(std::ptr::eq(matched[0].0, &near));
+    }
+
+    #[test]
+    fn query_entries_returns_nothing_outside_every_bbox() {
+        let mut tree = QuadTree::new(Rect { min_x: 0, min_y: 0, max_x: 1000, max_y: 1000 });
+
+        let only = feature();
+        tree.insert(&only, Point { x: 0, y: 0 }, Rect { min_x: 0, min_y: 0, max_x: 10, max_y: 10 });
+
+        let mut matched = Vec::new();
+        tree.query_entries(&Rect { min_x: 500, min_y: 500, max_x: 600, max_y: 600 }, &mut matched);
+
+        assert!(matched.is_empty());
+    }
+}
@@ -0,0 +1,262 @@
+use image::{Rgba, RgbaImage};
+
+use crate::quadtree::{QuadTree, Rect};
+use crate::style::StyleSheet;
+use crate::{Feature, GeometryType, Point, World};
+
+fn parse_color(name: &str) -> Rgba<u8> {
+    match name {
+        "blue" => Rgba([0, 0, 255, 255]),
+        "green" => Rgba([0, 128, 0, 255]),
+        "red" => Rgba([255, 0, 0, 255]),
+        "orange" => Rgba([255, 165, 0, 255]),
+        "yellow" => Rgba([255, 255, 0, 255]),
+        "white" => Rgba([255, 255, 255, 255]),
+        "gray" => Rgba([128, 128, 128, 255]),
+        _ => Rgba([0, 0, 0, 255]),
+    }
+}
+
+/// Fills a polygon (in pixel space) with the even-odd scanline rule: for
+/// each row, intersect the scanline with every polygon edge, sort the
+/// x-crossings, then fill the spans between consecutive pairs.
+fn scanline_fill_polygon(image: &mut RgbaImage, points: &[(f32, f32)], color: Rgba<u8>) {
+    if points.len() < 3 {
+        return;
+    }
+
+    let min_y = points.iter().map(|p| p.1).fold(f32::INFINITY, f32::min).floor().max(0.0) as u32;
+    let max_y = points
+        .iter()
+        .map(|p| p.1)
+        .fold(f32::NEG_INFINITY, f32::max)
+        .ceil()
+        .min(image.height() as f32 - 1.0) as u32;
+
+    for y in min_y..=max_y {
+        let scan_y = y as f32 + 0.5;
+        let mut crossings: Vec<f32> = Vec::new();
+
+        for i in 0..points.len() {
+            let (x1, y1) = points[i];
+            let (x2, y2) = points[(i + 1) % points.len()];
+
+            if (y1 <= scan_y && y2 > scan_y) || (y2 <= scan_y && y1 > scan_y) {
+                let t = (scan_y - y1) / (y2 - y1);
+                crossings.push(x1 + t * (x2 - x1));
+            }
+        }
+
+        crossings.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        for pair in crossings.chunks(2) {
+            if let [x_start, x_end] = pair {
+                let start = x_start.round().max(0.0) as u32;
+                let end = x_end.round().min(image.width() as f32).max(start as f32) as u32;
+                for x in start..end {
+                    image.put_pixel(x, y, color);
+                }
+            }
+        }
+    }
+}
+
+fn draw_line(image: &mut RgbaImage, (x1, y1): (f32, f32), (x2, y2): (f32, f32), color: Rgba<u8>) {
+    let steps = (x2 - x1).abs().max((y2 - y1).abs()).ceil().max(1.0) as u32;
+
+    for i in 0..=steps {
+        let t = i as f32 / steps as f32;
+        let x = (x1 + t * (x2 - x1)).round();
+        let y = (y1 + t * (y2 - y1)).round();
+
+        if x >= 0.0 && y >= 0.0 && (x as u32) < image.width() && (y as u32) < image.height() {
+            image.put_pixel(x as u32, y as u32, color);
+        }
+    }
+}
+
+fn draw_dot(image: &mut RgbaImage, (x, y): (f32, f32), color: Rgba<u8>, radius: i32) {
+    let cx = x.round() as i32;
+    let cy = y.round() as i32;
+
+    for dy in -radius..=radius {
+        for dx in -radius..=radius {
+            if dx * dx + dy * dy > radius * radius {
+                continue;
+            }
+
+            let px = cx + dx;
+            let py = cy + dy;
+            if px >= 0 && py >= 0 && (px as u32) < image.width() && (py as u32) < image.height() {
+                image.put_pixel(px as u32, py as u32, color);
+            }
+        }
+    }
+}
+
+struct RasterPolygon {
+    points: Vec<(f32, f32)>,
+    color: Rgba<u8>,
+    z_index: u32,
+}
+
+/// A polyline (in pixel space) together with its stroke color.
+type ColoredLine = (Vec<(f32, f32)>, Rgba<u8>);
+
+/// Repeats the first point at the end so a polygon outline drawn via
+/// `draw_line`'s consecutive-pair windows closes the loop.
+fn close_loop(mut points: Vec<(f32, f32)>) -> Vec<(f32, f32)> {
+    if let Some(&first) = points.first() {
+        points.push(first);
+    }
+    points
+}
+
+/// Renders `bounds` as an RGBA raster: polygons are filled by scanline in
+/// z-index order (so e.g. water never paints over a building, whatever order
+/// the two appear in the XML),
+/// then lines and `name_en` labels are stroked on top. Labels are drawn as
+/// dot markers rather than glyphs - full text rasterization is out of scope
+/// here, unlike the SVG path which can rely on the browser's text renderer.
+///
+/// Only features whose bounding box intersects `bounds` are visited, via the
+/// same quadtree `World::render_region` uses for SVG region exports, so a
+/// `--region` PNG doesn't pay for the whole world's features.
+pub fn render_raster(world: &World, styles: &StyleSheet, bounds: &Rect, width_px: u32, height_px: u32) -> Result<RgbaImage, Box<dyn std::error::Error>> {
+    if bounds.width() <= 0 || bounds.height() <= 0 {
+        return Err(format!("cannot rasterize a degenerate region: {}x{} pixels", bounds.width(), bounds.height()).into());
+    }
+
+    let mut image = RgbaImage::from_pixel(width_px, height_px, Rgba([255, 255, 255, 255]));
+
+    let scale_x = width_px as f32 / bounds.width() as f32;
+    let scale_y = height_px as f32 / bounds.height() as f32;
+
+    let to_pixel = |p: &Point| -> (f32, f32) {
+        (
+            (p.x - bounds.min_x) as f32 * scale_x,
+            (p.y - bounds.min_y) as f32 * scale_y,
+        )
+    };
+
+    let mut entries: Vec<(&Feature, Point, Rect)> = Vec::new();
+    for cell in &world.cell {
+        let bottom_left = cell.bottom_left();
+        for feature in &cell.feature {
+            let bbox = feature.bounding_box(&bottom_left);
+            entries.push((feature, bottom_left.clone(), bbox));
+        }
+    }
+
+    let mut tree = QuadTree::new(bounds.clone());
+    for (feature, bottom_left, bbox) in entries {
+        tree.insert(feature, bottom_left, bbox);
+    }
+
+    let mut matched: Vec<(&Feature, &Point)> = Vec::new();
+    tree.query_entries(bounds, &mut matched);
+
+    let mut polygons: Vec<RasterPolygon> = Vec::new();
+    let mut lines: Vec<ColoredLine> = Vec::new();
+    let mut labels: Vec<((f32, f32), Rgba<u8>)> = Vec::new();
+
+    for (feature, bottom_left) in matched {
+        let resolved = styles.resolve(&feature.properties);
+
+        match feature.geometry.geometry_type {
+            GeometryType::Polygon => {
+                for coordinate in &feature.geometry.coordinates {
+                    let points: Vec<(f32, f32)> =
+                        coordinate.point.iter().map(|p| to_pixel(&bottom_left.add(p))).collect();
+
+                    match &resolved.fill {
+                        Some(fill) => {
+                            polygons.push(RasterPolygon {
+                                points,
+                                color: parse_color(fill),
+                                z_index: resolved.z_index,
+                            });
+                        }
+                        // Unfilled in SVG means `fill="none"` with an outline, not
+                        // invisible - stroke it instead of dropping it.
+                        None => {
+                            let color = parse_color(resolved.stroke.as_deref().unwrap_or("black"));
+                            lines.push((close_loop(points), color));
+                        }
+                    }
+                }
+            }
+            GeometryType::LineString => {
+                let color = parse_color(resolved.stroke.as_deref().unwrap_or("gray"));
+
+                for coordinate in &feature.geometry.coordinates {
+                    let points = coordinate.point.iter().map(|p| to_pixel(&bottom_left.add(p))).collect();
+                    lines.push((points, color));
+                }
+            }
+            GeometryType::Point => {
+                if let Some(properties) = &feature.properties {
+                    for property in &properties.property {
+                        if property.name != "name_en" {
+                            continue;
+                        }
+
+                        let label_style = styles.resolve_property(property);
+                        let color = parse_color(label_style.fill.as_deref().unwrap_or("blue"));
+
+                        for coordinate in &feature.geometry.coordinates {
+                            if let Some(point) = coordinate.point.first() {
+                                labels.push((to_pixel(&bottom_left.add(point)), color));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    polygons.sort_by_key(|polygon| polygon.z_index);
+
+    for polygon in &polygons {
+        scanline_fill_polygon(&mut image, &polygon.points, polygon.color);
+    }
+
+    for (points, color) in &lines {
+        for pair in points.windows(2) {
+            draw_line(&mut image, pair[0], pair[1], *color);
+        }
+    }
+
+    for (point, color) in &labels {
+        draw_dot(&mut image, *point, *color, 3);
+    }
+
+    Ok(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_interior_and_leaves_exterior_untouched() {
+        let mut image = RgbaImage::from_pixel(10, 10, Rgba([255, 255, 255, 255]));
+        let square = [(2.0, 2.0), (7.0, 2.0), (7.0, 7.0), (2.0, 7.0)];
+
+        scanline_fill_polygon(&mut image, &square, Rgba([0, 0, 255, 255]));
+
+        assert_eq!(*image.get_pixel(4, 4), Rgba([0, 0, 255, 255]));
+        assert_eq!(*image.get_pixel(0, 0), Rgba([255, 255, 255, 255]));
+        assert_eq!(*image.get_pixel(9, 9), Rgba([255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn fewer_than_three_points_is_a_no_op() {
+        let mut image = RgbaImage::from_pixel(4, 4, Rgba([255, 255, 255, 255]));
+        let segment = [(0.0, 0.0), (3.0, 3.0)];
+
+        scanline_fill_polygon(&mut image, &segment, Rgba([0, 0, 0, 255]));
+
+        assert!(image.pixels().all(|p| *p == Rgba([255, 255, 255, 255])));
+    }
+}
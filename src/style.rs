@@ -0,0 +1,90 @@
+use serde::Deserialize;
+
+use crate::{Properties, Property};
+
+/// A fully-resolved set of rendering attributes for a feature.
+#[derive(Deserialize, Debug, Clone)]
+pub struct ResolvedStyle {
+    #[serde(default)]
+    pub fill: Option<String>,
+    #[serde(default)]
+    pub stroke: Option<String>,
+    #[serde(default)]
+    pub stroke_width: Option<u32>,
+    pub layer: String,
+    #[serde(default)]
+    pub font_size: Option<u32>,
+    #[serde(default)]
+    pub z_index: u32,
+}
+
+/// A single `(property name, optional value)` selector and the style it resolves to.
+#[derive(Deserialize, Debug, Clone)]
+pub struct StyleRule {
+    pub property: String,
+    #[serde(default)]
+    pub value: Option<String>,
+    /// Marks a rule that styles a label derived from one property (e.g.
+    /// `name_en`), not the feature's geometry. Such rules are only reachable
+    /// through `resolve_property`, which `render_point` calls directly for the
+    /// property it is labelling - `resolve` skips them so a named road or
+    /// building doesn't get styled as text.
+    #[serde(default)]
+    pub label_only: bool,
+    pub style: ResolvedStyle,
+}
+
+impl StyleRule {
+    fn matches(&self, properties: &Properties) -> bool {
+        properties.property.iter().any(|property| self.matches_property(property))
+    }
+
+    fn matches_property(&self, property: &Property) -> bool {
+        property.name == self.property
+            && match &self.value {
+                Some(value) => *value == property.value,
+                None => true,
+            }
+    }
+}
+
+/// Ordered table of style rules loaded from a TOML file, replacing the
+/// hard-coded `if property.name == ...` chains that used to live in
+/// `Coordinates::render_*`.
+#[derive(Deserialize, Debug)]
+pub struct StyleSheet {
+    #[serde(rename = "rule", default)]
+    pub rules: Vec<StyleRule>,
+    pub default: ResolvedStyle,
+}
+
+impl StyleSheet {
+    pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let contents = std::fs::read_to_string(path)?;
+        Ok(toml::from_str(&contents)?)
+    }
+
+    /// Returns the style of the first rule (in file order) whose selector
+    /// matches any property on the feature, falling back to `default`.
+    pub fn resolve(&self, properties: &Option<Properties>) -> ResolvedStyle {
+        if let Some(properties) = properties {
+            for rule in &self.rules {
+                if !rule.label_only && rule.matches(properties) {
+                    return rule.style.clone();
+                }
+            }
+        }
+
+        self.default.clone()
+    }
+
+    /// Resolves the style for a single property in isolation, e.g. to style
+    /// a label derived from one property on a feature that may carry others.
+    pub fn resolve_property(&self, property: &Property) -> ResolvedStyle {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches_property(property))
+            .map(|rule| rule.style.clone())
+            .unwrap_or_else(|| self.default.clone())
+    }
+}
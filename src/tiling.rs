@@ -0,0 +1,72 @@
+use crate::quadtree::{QuadTree, Rect};
+use crate::style::StyleSheet;
+use crate::{Feature, Point, SVGLayers, World};
+
+/// Parameters for a tiled pyramid export: `base_tile_size` is the tile's
+/// world-pixel width/height at zoom 0, halved at each subsequent zoom level
+/// (the classic slippy-map bucketing scheme), for zoom levels 0 through
+/// `max_zoom` inclusive.
+pub struct PyramidConfig {
+    pub base_tile_size: i32,
+    pub max_zoom: u32,
+}
+
+impl Default for PyramidConfig {
+    fn default() -> Self {
+        Self {
+            base_tile_size: 2048,
+            max_zoom: 4,
+        }
+    }
+}
+
+/// Buckets every feature into a `z/x/y` grid of tiles and writes one SVG per
+/// tile under `out_dir`, so the output can be served directly to a
+/// Leaflet-style viewer instead of one unpannable multi-megabyte SVG.
+pub fn export_pyramid(world: &World, styles: &StyleSheet, world_bounds: &Rect, config: &PyramidConfig, out_dir: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut entries: Vec<(&Feature, Point, Rect)> = Vec::new();
+    for cell in &world.cell {
+        let bottom_left = cell.bottom_left();
+        for feature in &cell.feature {
+            let bbox = feature.bounding_box(&bottom_left);
+            entries.push((feature, bottom_left.clone(), bbox));
+        }
+    }
+
+    let mut tree = QuadTree::new(world_bounds.clone());
+    for (feature, bottom_left, bbox) in entries {
+        tree.insert(feature, bottom_left, bbox);
+    }
+
+    for zoom in 0..=config.max_zoom {
+        let tile_size = config.base_tile_size >> zoom;
+        if tile_size <= 0 {
+            break;
+        }
+
+        let min_tile_x = world_bounds.min_x.div_euclid(tile_size);
+        let max_tile_x = (world_bounds.max_x - 1).div_euclid(tile_size);
+        let min_tile_y = world_bounds.min_y.div_euclid(tile_size);
+        let max_tile_y = (world_bounds.max_y - 1).div_euclid(tile_size);
+
+        for tile_x in min_tile_x..=max_tile_x {
+            for tile_y in min_tile_y..=max_tile_y {
+                let tile_rect = Rect {
+                    min_x: tile_x * tile_size,
+                    min_y: tile_y * tile_size,
+                    max_x: (tile_x + 1) * tile_size,
+                    max_y: (tile_y + 1) * tile_size,
+                };
+
+                let mut tile_layers = SVGLayers::new(tile_rect.min_x, tile_rect.min_y, tile_rect.max_x, tile_rect.max_y);
+                tree.query_region(&tile_rect, &mut tile_layers, styles);
+
+                let tile_dir = format!("{out_dir}/{zoom}/{tile_x}");
+                std::fs::create_dir_all(&tile_dir)?;
+                tile_layers.save_combined(&format!("{tile_dir}/{tile_y}.svg"))?;
+            }
+        }
+    }
+
+    Ok(())
+}